@@ -1,9 +1,158 @@
 //! CX Terminal: Create new projects from templates
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context};
 use clap::Parser;
+use directories::ProjectDirs;
+use serde::Deserialize;
+
+/// Name of the optional manifest at the root of a template that declares
+/// variables, the files to render, and post-generation hooks.
+const TEMPLATE_MANIFEST: &str = "cx-template.toml";
+
+/// Declaration of a single template variable.
+#[derive(Debug, Clone, Deserialize)]
+struct VarSpec {
+    /// Text shown when prompting for the value interactively.
+    prompt: Option<String>,
+    /// Default used when the variable is not supplied on the command line.
+    default: Option<String>,
+}
+
+/// Post-generation hooks run in the new project directory.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct Hooks {
+    /// Shell commands run, in order, after the tree is rendered.
+    #[serde(default)]
+    post: Vec<String>,
+}
+
+/// Parsed `cx-template.toml`.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct TemplateManifest {
+    /// Declared variables keyed by placeholder name.
+    #[serde(default)]
+    variables: BTreeMap<String, VarSpec>,
+    /// Files whose contents should be rendered. When empty, every file is
+    /// rendered; file and directory names are always rendered.
+    #[serde(default)]
+    files: Vec<String>,
+    #[serde(default)]
+    hooks: Hooks,
+}
+
+/// Year (UTC) derived from the current time, for the built-in `{{year}}`.
+fn current_year() -> u64 {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    // Days since the epoch, walked year by year accounting for leap years.
+    let mut days = secs / 86_400;
+    let mut year = 1970u64;
+    loop {
+        let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+        let in_year = if leap { 366 } else { 365 };
+        if days < in_year {
+            break;
+        }
+        days -= in_year;
+        year += 1;
+    }
+    year
+}
+
+/// Replace every `{{key}}` occurrence in `input` with its value.
+fn substitute(input: &str, vars: &BTreeMap<String, String>) -> String {
+    let mut out = input.to_string();
+    for (key, value) in vars {
+        out = out.replace(&format!("{{{{{key}}}}}"), value);
+    }
+    out
+}
+
+/// Built-in registry mapping short template names to the Git repositories
+/// that hold their scaffolds. Users can still pass a full repo URL in place
+/// of a short name, so this is a convenience layer rather than a closed enum.
+const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    ("rust", "https://github.com/cortexlinux/template-rust"),
+    ("python", "https://github.com/cortexlinux/template-python"),
+    ("node", "https://github.com/cortexlinux/template-node"),
+    ("default", "https://github.com/cortexlinux/template-default"),
+];
+
+/// Resolve a template name to a repository URL. A value containing `://` or
+/// starting with `git@` is treated as an explicit URL; otherwise it is looked
+/// up in the built-in registry.
+fn resolve_repo(template: &str) -> anyhow::Result<String> {
+    if template.contains("://") || template.starts_with("git@") {
+        return Ok(template.to_string());
+    }
+    BUILTIN_TEMPLATES
+        .iter()
+        .find(|(name, _)| *name == template)
+        .map(|(_, url)| url.to_string())
+        .with_context(|| format!("unknown template '{template}' (not in the built-in registry)"))
+}
+
+/// Directory under which cached templates are cloned, e.g.
+/// `~/.cache/cortex/templates`.
+fn templates_cache_dir() -> anyhow::Result<PathBuf> {
+    let dirs = ProjectDirs::from("org", "cortexlinux", "cortex")
+        .context("could not determine a cache directory for this platform")?;
+    Ok(dirs.cache_dir().join("templates"))
+}
+
+/// Recursively render `src` into `dst`. File and directory names have their
+/// `{{placeholders}}` substituted from `vars`; a file's *contents* are
+/// rendered too when `render` reports its template-relative path. The `.git`
+/// directory of a cloned template and the template manifest itself are
+/// skipped so generated projects start clean.
+fn render_tree(
+    src: &Path,
+    dst: &Path,
+    rel: &Path,
+    vars: &BTreeMap<String, String>,
+    render: &dyn Fn(&Path) -> bool,
+) -> anyhow::Result<()> {
+    fs::create_dir_all(dst).with_context(|| format!("creating {}", dst.display()))?;
+    for entry in fs::read_dir(src).with_context(|| format!("reading {}", src.display()))? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name == ".git" || name == TEMPLATE_MANIFEST {
+            continue;
+        }
+        let from = entry.path();
+        let rendered_name = substitute(&name.to_string_lossy(), vars);
+        let to = dst.join(&rendered_name);
+        let child_rel = rel.join(&name);
+        if entry.file_type()?.is_dir() {
+            render_tree(&from, &to, &child_rel, vars, render)?;
+        } else if render(&child_rel) {
+            // Only text files are rendered; a binary asset (image, jar, …)
+            // is not valid UTF-8 and is copied through byte-for-byte.
+            match fs::read_to_string(&from) {
+                Ok(contents) => fs::write(&to, substitute(&contents, vars))
+                    .with_context(|| format!("writing {}", to.display()))?,
+                Err(_) => {
+                    fs::copy(&from, &to).with_context(|| format!("copying {}", from.display()))?;
+                }
+            }
+        } else {
+            fs::copy(&from, &to).with_context(|| format!("copying {}", from.display()))?;
+        }
+    }
+    Ok(())
+}
 
 #[derive(Debug, Parser, Clone)]
 pub struct NewCommand {
-    /// The template to use (e.g., "rust", "python", "node")
+    /// The template to use (e.g., "rust", "python", "node") or a Git repo URL
     #[arg(default_value = "default")]
     pub template: String,
 
@@ -14,15 +163,167 @@ pub struct NewCommand {
     /// The directory to create the project in
     #[arg(short, long)]
     pub dir: Option<String>,
+
+    /// Use the cached template without touching the network
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Skip the template's post-generation hooks
+    #[arg(long)]
+    pub no_hooks: bool,
 }
 
 impl NewCommand {
+    /// Ensure the template is present in the cache and return its path.
+    ///
+    /// Mirrors how gitig caches GitHub-hosted `.gitignore` templates: the
+    /// first use shallow-clones into the cache, and later uses pull to refresh
+    /// it — unless `--offline` is set, in which case the cache is trusted as-is
+    /// and a missing entry is a hard error.
+    fn ensure_cached(&self) -> anyhow::Result<PathBuf> {
+        let cache = templates_cache_dir()?.join(&self.template);
+
+        if self.offline {
+            if !cache.exists() {
+                bail!(
+                    "template '{}' is not in the cache and --offline was given",
+                    self.template
+                );
+            }
+            return Ok(cache);
+        }
+
+        let repo = resolve_repo(&self.template)?;
+        if cache.exists() {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(&cache)
+                .args(["pull", "--ff-only"])
+                .status()
+                .context("failed to run git; is it installed and on PATH?")?;
+            if !status.success() {
+                bail!("git pull failed for template '{}'", self.template);
+            }
+        } else {
+            if let Some(parent) = cache.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let status = Command::new("git")
+                .args(["clone", "--depth", "1", &repo])
+                .arg(&cache)
+                .status()
+                .context("failed to run git; is it installed and on PATH?")?;
+            if !status.success() {
+                bail!("git clone failed for template '{}'", self.template);
+            }
+        }
+        Ok(cache)
+    }
+
     pub fn run(&self) -> anyhow::Result<()> {
+        let project_name = self
+            .name
+            .clone()
+            .unwrap_or_else(|| self.template.clone());
+        let target = PathBuf::from(self.dir.clone().unwrap_or_else(|| project_name.clone()));
+        if target.exists() {
+            bail!("target directory {} already exists", target.display());
+        }
+
+        let cached = self.ensure_cached()?;
+        let manifest = self.load_template_manifest(&cached)?;
+        let vars = resolve_vars(&manifest, &project_name)?;
+
+        let render_set: Vec<PathBuf> =
+            manifest.files.iter().map(PathBuf::from).collect();
+        let render_all = render_set.is_empty();
+        let render = move |rel: &Path| render_all || render_set.iter().any(|f| f == rel);
+        render_tree(&cached, &target, Path::new(""), &vars, &render)?;
+
         eprintln!(
-            "CX Terminal: 'new' command is not yet implemented. Template: {}",
-            self.template
+            "Created project from template '{}' in {}",
+            self.template,
+            target.display()
         );
-        eprintln!("This feature will create new projects from templates.");
+
+        if !self.no_hooks {
+            run_hooks(&manifest.hooks, &target)?;
+        }
         Ok(())
     }
+
+    /// Parse `cx-template.toml` at the template root, defaulting to an empty
+    /// manifest when the template does not ship one. With no declared `files`,
+    /// every text file is still rendered for `{{placeholder}}`s while binary
+    /// assets are copied through untouched.
+    fn load_template_manifest(&self, template: &Path) -> anyhow::Result<TemplateManifest> {
+        let path = template.join(TEMPLATE_MANIFEST);
+        match fs::read_to_string(&path) {
+            Ok(data) => {
+                toml::from_str(&data).with_context(|| format!("parsing {}", path.display()))
+            }
+            Err(_) => Ok(TemplateManifest::default()),
+        }
+    }
+}
+
+/// Build the substitution map from the built-in variables plus the manifest's
+/// declared variables, prompting for any required value that has no default.
+fn resolve_vars(
+    manifest: &TemplateManifest,
+    project_name: &str,
+) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut vars = BTreeMap::new();
+    vars.insert("project_name".to_string(), project_name.to_string());
+    vars.insert(
+        "author".to_string(),
+        std::env::var("USER").unwrap_or_default(),
+    );
+    vars.insert("year".to_string(), current_year().to_string());
+
+    for (name, spec) in &manifest.variables {
+        if name == "project_name" {
+            // Taken from --name (or the template name); a default never overrides it.
+            continue;
+        } else if let Some(default) = &spec.default {
+            vars.insert(name.clone(), default.clone());
+        } else if vars.contains_key(name) {
+            // A built-in (e.g. author) already provides a value.
+            continue;
+        } else {
+            vars.insert(name.clone(), prompt_for(name, spec)?);
+        }
+    }
+    Ok(vars)
+}
+
+/// Interactively read a value for a required variable, failing when stdin is
+/// not a terminal so non-interactive runs do not hang.
+fn prompt_for(name: &str, spec: &VarSpec) -> anyhow::Result<String> {
+    if !std::io::stdin().is_terminal() {
+        bail!("variable '{name}' has no default and no value was provided");
+    }
+    let label = spec.prompt.as_deref().unwrap_or(name);
+    eprint!("{label}: ");
+    std::io::stderr().flush().ok();
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Run each post-generation hook as a shell command in the project directory.
+fn run_hooks(hooks: &Hooks, dir: &Path) -> anyhow::Result<()> {
+    for cmd in &hooks.post {
+        eprintln!("hook: {cmd}");
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(cmd)
+            .current_dir(dir)
+            .status()
+            .with_context(|| format!("running hook '{cmd}'"))?;
+        if !status.success() {
+            bail!("hook '{cmd}' failed");
+        }
+    }
+    Ok(())
 }
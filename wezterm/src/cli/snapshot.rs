@@ -1,5 +1,175 @@
 //! CX Terminal: Workspace snapshot management
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context};
 use clap::Parser;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Root of the content-addressed store inside a workspace.
+const STORE_DIR: &str = ".cx";
+
+/// A single file recorded in a snapshot manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// Path of the file relative to the workspace root.
+    pub path: String,
+    /// Unix permission bits of the file at capture time.
+    pub mode: u32,
+    /// Hex-encoded SHA-256 of the file's bytes; also its key in the object store.
+    pub hash: String,
+    /// Size of the file in bytes.
+    pub size: u64,
+}
+
+/// The JSON document written to `.cx/snapshots/<name>.json`.
+///
+/// Blobs are deduplicated by hash across snapshots, so a manifest is only a
+/// list of references into the shared object store — capturing a second
+/// snapshot costs disk only for the files that actually changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub name: String,
+    pub description: Option<String>,
+    /// Seconds since the Unix epoch at which the snapshot was taken.
+    pub timestamp: u64,
+    /// Name of the snapshot this one was taken on top of, if any.
+    pub parent_snapshot: Option<String>,
+    pub entries: Vec<Entry>,
+}
+
+/// Directory holding the deduplicated blobs, e.g. `.cx/objects`.
+fn objects_dir(root: &Path) -> PathBuf {
+    root.join(STORE_DIR).join("objects")
+}
+
+/// Directory holding the snapshot manifests, e.g. `.cx/snapshots`.
+fn snapshots_dir(root: &Path) -> PathBuf {
+    root.join(STORE_DIR).join("snapshots")
+}
+
+/// Location of the manifest for a named snapshot.
+fn manifest_path(root: &Path, name: &str) -> PathBuf {
+    snapshots_dir(root).join(format!("{name}.json"))
+}
+
+/// Fanned-out location of a blob: `.cx/objects/<first2>/<hash>`.
+fn object_path(root: &Path, hash: &str) -> PathBuf {
+    objects_dir(root).join(&hash[..2]).join(hash)
+}
+
+/// Hex-encode a byte slice.
+fn to_hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        s.push_str(&format!("{b:02x}"));
+    }
+    s
+}
+
+/// SHA-256 of a byte slice, hex-encoded.
+fn hash_bytes(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    to_hex(&hasher.finalize())
+}
+
+/// Read and parse a manifest by name.
+fn load_manifest(root: &Path, name: &str) -> anyhow::Result<Manifest> {
+    let path = manifest_path(root, name);
+    let data = fs::read_to_string(&path)
+        .with_context(|| format!("no such snapshot '{name}' at {}", path.display()))?;
+    let manifest: Manifest =
+        serde_json::from_str(&data).with_context(|| format!("corrupt manifest {}", path.display()))?;
+    Ok(manifest)
+}
+
+/// Unix permission bits for a path, or `0o644` as a portable fallback.
+fn file_mode(meta: &fs::Metadata) -> u32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        meta.permissions().mode() & 0o7777
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = meta;
+        0o644
+    }
+}
+
+/// A minimal `.cxignore`/`.gitignore`-style exclusion set.
+///
+/// Supports blank lines, `#` comments, and plain path or basename matches.
+/// A trailing `/` is stripped, so such a pattern matches files as well as
+/// directories. The store directory and
+/// `.git` are always excluded so snapshots never capture themselves.
+struct IgnoreSet {
+    patterns: Vec<String>,
+}
+
+impl IgnoreSet {
+    fn load(root: &Path) -> Self {
+        let mut patterns = vec![STORE_DIR.to_string(), ".git".to_string()];
+        for file in [".cxignore", ".gitignore"] {
+            if let Ok(contents) = fs::read_to_string(root.join(file)) {
+                for line in contents.lines() {
+                    let line = line.trim();
+                    if line.is_empty() || line.starts_with('#') {
+                        continue;
+                    }
+                    patterns.push(line.trim_end_matches('/').to_string());
+                }
+            }
+        }
+        Self { patterns }
+    }
+
+    /// Whether `rel` (a workspace-relative path) is excluded.
+    fn is_ignored(&self, rel: &Path) -> bool {
+        let rel_str = rel.to_string_lossy();
+        let name = rel
+            .file_name()
+            .map(|n| n.to_string_lossy())
+            .unwrap_or_default();
+        self.patterns.iter().any(|p| {
+            rel_str == p.as_str()
+                || name == p.as_str()
+                || rel_str.starts_with(&format!("{p}/"))
+        })
+    }
+}
+
+/// Recursively collect the workspace-relative paths of all non-ignored files.
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    ignore: &IgnoreSet,
+    out: &mut Vec<PathBuf>,
+) -> anyhow::Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+        let entry = entry?;
+        let path = entry.path();
+        let rel = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_path_buf();
+        if ignore.is_ignored(&rel) {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(root, &path, ignore, out)?;
+        } else if file_type.is_file() {
+            out.push(rel);
+        }
+    }
+    Ok(())
+}
 
 #[derive(Debug, Parser, Clone)]
 pub struct SaveCommand {
@@ -10,12 +180,74 @@ pub struct SaveCommand {
     /// Description of the snapshot
     #[arg(short, long)]
     pub description: Option<String>,
+
+    /// Name of the snapshot this one builds on
+    #[arg(short, long)]
+    pub parent: Option<String>,
 }
 
 impl SaveCommand {
     pub fn run(&self) -> anyhow::Result<()> {
-        eprintln!("CX Terminal: 'save' command is not yet implemented.");
-        eprintln!("This feature will save current workspace as a snapshot.");
+        let root = std::env::current_dir()?;
+        let name = self
+            .name
+            .clone()
+            .context("a snapshot name is required (pass --name)")?;
+
+        let ignore = IgnoreSet::load(&root);
+        let mut files = Vec::new();
+        collect_files(&root, &root, &ignore, &mut files)?;
+        files.sort();
+
+        let mut entries = Vec::with_capacity(files.len());
+        let mut written = 0usize;
+        for rel in &files {
+            let abs = root.join(rel);
+            let meta = fs::symlink_metadata(&abs)?;
+            let bytes = fs::read(&abs).with_context(|| format!("reading {}", abs.display()))?;
+            let hash = hash_bytes(&bytes);
+
+            // Content addressing: only write the blob if it is not already present.
+            let blob = object_path(&root, &hash);
+            if !blob.exists() {
+                if let Some(parent) = blob.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&blob, &bytes)
+                    .with_context(|| format!("writing object {}", blob.display()))?;
+                written += 1;
+            }
+
+            entries.push(Entry {
+                path: rel.to_string_lossy().into_owned(),
+                mode: file_mode(&meta),
+                hash,
+                size: bytes.len() as u64,
+            });
+        }
+
+        let manifest = Manifest {
+            name: name.clone(),
+            description: self.description.clone(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            parent_snapshot: self.parent.clone(),
+            entries,
+        };
+
+        fs::create_dir_all(snapshots_dir(&root))?;
+        let path = manifest_path(&root, &name);
+        let json = serde_json::to_string_pretty(&manifest)?;
+        fs::write(&path, json).with_context(|| format!("writing manifest {}", path.display()))?;
+
+        eprintln!(
+            "Saved snapshot '{}': {} files, {} new object(s)",
+            name,
+            manifest.entries.len(),
+            written
+        );
         Ok(())
     }
 }
@@ -24,19 +256,141 @@ impl SaveCommand {
 pub struct RestoreCommand {
     /// Name of the snapshot to restore
     pub name: String,
+
+    /// Overwrite existing files in the working tree
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Delete working-tree files that are absent from the snapshot
+    #[arg(long)]
+    pub clean: bool,
 }
 
 impl RestoreCommand {
     pub fn run(&self) -> anyhow::Result<()> {
+        let root = std::env::current_dir()?;
+        let manifest = load_manifest(&root, &self.name)?;
+
+        if self.dry_run {
+            return self.preview(&root, &manifest);
+        }
+
+        if !self.force {
+            for entry in &manifest.entries {
+                // Only block on files that would actually be overwritten — i.e.
+                // whose on-disk contents differ from the snapshot. This matches
+                // what `--dry-run` classifies as `M`, so preview and apply agree.
+                if let Ok(bytes) = fs::read(root.join(&entry.path)) {
+                    if hash_bytes(&bytes) != entry.hash {
+                        bail!(
+                            "{} already exists; pass --force to overwrite the working tree",
+                            entry.path
+                        );
+                    }
+                }
+            }
+        }
+
+        for entry in &manifest.entries {
+            let blob = object_path(&root, &entry.hash);
+            let bytes = fs::read(&blob)
+                .with_context(|| format!("missing object {} for {}", entry.hash, entry.path))?;
+
+            // Detect object-store corruption before trusting the bytes.
+            let actual = hash_bytes(&bytes);
+            if actual != entry.hash {
+                bail!(
+                    "object store corruption: {} hashes to {} but manifest expects {}",
+                    entry.path,
+                    actual,
+                    entry.hash
+                );
+            }
+
+            let dest = root.join(&entry.path);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&dest, &bytes).with_context(|| format!("writing {}", dest.display()))?;
+
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&dest, fs::Permissions::from_mode(entry.mode))?;
+            }
+        }
+
+        if self.clean {
+            for rel in extraneous(&root, &manifest)? {
+                fs::remove_file(root.join(&rel))
+                    .with_context(|| format!("removing {}", rel.display()))?;
+            }
+        }
+
+        eprintln!(
+            "Restored snapshot '{}': {} files",
+            self.name,
+            manifest.entries.len()
+        );
+        let _ = std::io::stderr().flush();
+        Ok(())
+    }
+
+    /// Classify the delta between the snapshot and the live working tree and
+    /// print a per-path status line (`M`/`A`/`D`) plus a summary, writing
+    /// nothing. `M` = contents differ, `A` = missing on disk, `D` = present on
+    /// disk but absent from the snapshot (deleted only with `--clean`).
+    fn preview(&self, root: &Path, manifest: &Manifest) -> anyhow::Result<()> {
+        let (mut modified, mut added, mut deleted) = (0usize, 0usize, 0usize);
+
+        for entry in &manifest.entries {
+            let dest = root.join(&entry.path);
+            match fs::read(&dest) {
+                Ok(bytes) => {
+                    if hash_bytes(&bytes) != entry.hash {
+                        println!("M {}", entry.path);
+                        modified += 1;
+                    }
+                }
+                Err(_) => {
+                    println!("A {}", entry.path);
+                    added += 1;
+                }
+            }
+        }
+
+        for rel in extraneous(root, manifest)? {
+            println!("D {}", rel.display());
+            deleted += 1;
+        }
+
+        let fate = if self.clean {
+            "to delete"
+        } else {
+            "untouched"
+        };
         eprintln!(
-            "CX Terminal: 'restore' command is not yet implemented. Snapshot: {}",
-            self.name
+            "{modified} modified, {added} to create, {deleted} extra ({fate})"
         );
-        eprintln!("This feature will restore a workspace from a snapshot.");
         Ok(())
     }
 }
 
+/// Working-tree files that are not recorded in `manifest`, relative to `root`.
+fn extraneous(root: &Path, manifest: &Manifest) -> anyhow::Result<Vec<PathBuf>> {
+    let recorded: HashSet<&str> = manifest.entries.iter().map(|e| e.path.as_str()).collect();
+    let ignore = IgnoreSet::load(root);
+    let mut files = Vec::new();
+    collect_files(root, root, &ignore, &mut files)?;
+    files.retain(|rel| !recorded.contains(rel.to_string_lossy().as_ref()));
+    files.sort();
+    Ok(files)
+}
+
 #[derive(Debug, Parser, Clone)]
 pub struct SnapshotsCommand {
     /// List all snapshots
@@ -46,12 +400,299 @@ pub struct SnapshotsCommand {
     /// Delete a snapshot by name
     #[arg(short, long)]
     pub delete: Option<String>,
+
+    /// Garbage-collect objects no longer referenced by any snapshot
+    #[arg(long)]
+    pub gc: bool,
+
+    /// Delete the oldest snapshots beyond the newest `--keep`
+    #[arg(long)]
+    pub prune: bool,
+
+    /// Number of snapshots to retain when pruning
+    #[arg(long)]
+    pub keep: Option<usize>,
+}
+
+/// Every snapshot manifest, parsed and sorted oldest-first by timestamp.
+fn list_snapshots(root: &Path) -> anyhow::Result<Vec<Manifest>> {
+    let dir = snapshots_dir(root);
+    let mut manifests = Vec::new();
+    if let Ok(entries) = fs::read_dir(&dir) {
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("json") {
+                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    manifests.push(load_manifest(root, stem)?);
+                }
+            }
+        }
+    }
+    manifests.sort_by_key(|m| m.timestamp);
+    Ok(manifests)
+}
+
+/// Count how many manifests reference each blob hash.
+fn reference_counts(manifests: &[Manifest]) -> std::collections::HashMap<String, usize> {
+    let mut counts = std::collections::HashMap::new();
+    for manifest in manifests {
+        let mut seen = HashSet::new();
+        for entry in &manifest.entries {
+            // Count a hash once per manifest, even if it appears twice within.
+            if seen.insert(entry.hash.as_str()) {
+                *counts.entry(entry.hash.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
 }
 
 impl SnapshotsCommand {
     pub fn run(&self) -> anyhow::Result<()> {
-        eprintln!("CX Terminal: 'snapshots' command is not yet implemented.");
-        eprintln!("This feature will list and manage workspace snapshots.");
+        let root = std::env::current_dir()?;
+
+        if let Some(name) = &self.delete {
+            let path = manifest_path(&root, name);
+            fs::remove_file(&path)
+                .with_context(|| format!("no such snapshot '{name}' at {}", path.display()))?;
+            eprintln!("Deleted snapshot '{name}'");
+        }
+
+        if self.prune {
+            let keep = self.keep.context("--prune requires --keep <N>")?;
+            let manifests = list_snapshots(&root)?;
+            if manifests.len() > keep {
+                let drop = manifests.len() - keep;
+                for manifest in &manifests[..drop] {
+                    fs::remove_file(manifest_path(&root, &manifest.name))?;
+                    eprintln!("Pruned snapshot '{}'", manifest.name);
+                }
+            }
+        }
+
+        if self.gc {
+            self.collect_garbage(&root)?;
+        }
+
+        if self.list {
+            self.print_list(&root)?;
+        }
+
+        Ok(())
+    }
+
+    /// Print each snapshot with its timestamp, entry count, and the split
+    /// between bytes it uniquely owns and bytes it shares with others.
+    fn print_list(&self, root: &Path) -> anyhow::Result<()> {
+        let manifests = list_snapshots(root)?;
+        let counts = reference_counts(&manifests);
+        for manifest in &manifests {
+            let (mut unique, mut shared) = (0u64, 0u64);
+            let mut seen = HashSet::new();
+            for entry in &manifest.entries {
+                if !seen.insert(entry.hash.as_str()) {
+                    continue;
+                }
+                if counts.get(&entry.hash).copied().unwrap_or(0) > 1 {
+                    shared += entry.size;
+                } else {
+                    unique += entry.size;
+                }
+            }
+            println!(
+                "{}\t{}\t{} files\t{} unique / {} shared bytes",
+                manifest.name,
+                manifest.timestamp,
+                manifest.entries.len(),
+                unique,
+                shared
+            );
+        }
         Ok(())
     }
+
+    /// Mark-and-sweep the object store: enumerate *every* surviving manifest
+    /// to build the set of live hashes first, then unlink any blob not in it.
+    /// Enumerating before deleting is what keeps blobs shared between a pruned
+    /// and a retained snapshot from being dropped.
+    fn collect_garbage(&self, root: &Path) -> anyhow::Result<()> {
+        let manifests = list_snapshots(root)?;
+        let mut live: HashSet<String> = HashSet::new();
+        for manifest in &manifests {
+            for entry in &manifest.entries {
+                live.insert(entry.hash.clone());
+            }
+        }
+
+        let objects = objects_dir(root);
+        let mut removed = 0usize;
+        if let Ok(shards) = fs::read_dir(&objects) {
+            for shard in shards {
+                let shard = shard?.path();
+                if !shard.is_dir() {
+                    continue;
+                }
+                for blob in fs::read_dir(&shard)? {
+                    let blob = blob?.path();
+                    let hash = blob.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                    if !live.contains(hash) {
+                        fs::remove_file(&blob)
+                            .with_context(|| format!("removing {}", blob.display()))?;
+                        removed += 1;
+                    }
+                }
+            }
+        }
+        eprintln!("Garbage collection removed {removed} unreferenced object(s)");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    // `std::env::set_current_dir` mutates process-global state, so the tests
+    // that drive the commands through the working directory run serially.
+    static CWD_LOCK: Mutex<()> = Mutex::new(());
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    /// A unique empty scratch directory under the system temp dir.
+    fn temp_workspace() -> PathBuf {
+        let id = COUNTER.fetch_add(1, Ordering::SeqCst);
+        let dir = std::env::temp_dir().join(format!("cx-test-{}-{}", std::process::id(), id));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn save_restore_round_trip() {
+        let _guard = CWD_LOCK.lock().unwrap();
+        let ws = temp_workspace();
+        fs::write(ws.join("a.txt"), b"hello").unwrap();
+        fs::create_dir_all(ws.join("sub")).unwrap();
+        fs::write(ws.join("sub/b.txt"), b"world").unwrap();
+
+        std::env::set_current_dir(&ws).unwrap();
+        SaveCommand {
+            name: Some("snap".into()),
+            description: None,
+            parent: None,
+        }
+        .run()
+        .unwrap();
+
+        fs::remove_file(ws.join("a.txt")).unwrap();
+        fs::remove_dir_all(ws.join("sub")).unwrap();
+
+        RestoreCommand {
+            name: "snap".into(),
+            force: false,
+            dry_run: false,
+            clean: false,
+        }
+        .run()
+        .unwrap();
+
+        assert_eq!(fs::read(ws.join("a.txt")).unwrap(), b"hello");
+        assert_eq!(fs::read(ws.join("sub/b.txt")).unwrap(), b"world");
+        fs::remove_dir_all(&ws).ok();
+    }
+
+    #[test]
+    fn restore_detects_object_corruption() {
+        let _guard = CWD_LOCK.lock().unwrap();
+        let ws = temp_workspace();
+        fs::write(ws.join("a.txt"), b"hello").unwrap();
+
+        std::env::set_current_dir(&ws).unwrap();
+        SaveCommand {
+            name: Some("snap".into()),
+            description: None,
+            parent: None,
+        }
+        .run()
+        .unwrap();
+
+        // Flip the bytes of the sole blob without updating the manifest hash.
+        let hash = hash_bytes(b"hello");
+        fs::write(object_path(&ws, &hash), b"tampered").unwrap();
+        fs::remove_file(ws.join("a.txt")).unwrap();
+
+        let err = RestoreCommand {
+            name: "snap".into(),
+            force: false,
+            dry_run: false,
+            clean: false,
+        }
+        .run()
+        .unwrap_err();
+        assert!(err.to_string().contains("corruption"));
+        fs::remove_dir_all(&ws).ok();
+    }
+
+    #[test]
+    fn gc_preserves_blob_shared_with_retained_snapshot() {
+        let ws = temp_workspace();
+        let shared = hash_bytes(b"shared");
+        let only_old = hash_bytes(b"old-only");
+
+        // Write both blobs to the object store.
+        for (hash, bytes) in [(&shared, &b"shared"[..]), (&only_old, &b"old-only"[..])] {
+            let blob = object_path(&ws, hash);
+            fs::create_dir_all(blob.parent().unwrap()).unwrap();
+            fs::write(&blob, bytes).unwrap();
+        }
+
+        // "old" references both blobs; "new" references only the shared one.
+        fs::create_dir_all(snapshots_dir(&ws)).unwrap();
+        let old = Manifest {
+            name: "old".into(),
+            description: None,
+            timestamp: 1,
+            parent_snapshot: None,
+            entries: vec![
+                Entry { path: "s".into(), mode: 0o644, hash: shared.clone(), size: 6 },
+                Entry { path: "o".into(), mode: 0o644, hash: only_old.clone(), size: 8 },
+            ],
+        };
+        let new = Manifest {
+            name: "new".into(),
+            description: None,
+            timestamp: 2,
+            parent_snapshot: None,
+            entries: vec![Entry {
+                path: "s".into(),
+                mode: 0o644,
+                hash: shared.clone(),
+                size: 6,
+            }],
+        };
+        for m in [&old, &new] {
+            fs::write(manifest_path(&ws, &m.name), serde_json::to_string(m).unwrap()).unwrap();
+        }
+
+        // Prune down to the newest one, then GC.
+        let cmd = SnapshotsCommand {
+            list: false,
+            delete: None,
+            gc: true,
+            prune: true,
+            keep: Some(1),
+        };
+        // Prune "old", leaving "new".
+        let manifests = list_snapshots(&ws).unwrap();
+        let drop = manifests.len() - 1;
+        for m in &manifests[..drop] {
+            fs::remove_file(manifest_path(&ws, &m.name)).unwrap();
+        }
+        cmd.collect_garbage(&ws).unwrap();
+
+        // The shared blob survives; the pruned-only blob is swept.
+        assert!(object_path(&ws, &shared).exists());
+        assert!(!object_path(&ws, &only_old).exists());
+        fs::remove_dir_all(&ws).ok();
+    }
 }